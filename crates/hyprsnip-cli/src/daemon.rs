@@ -0,0 +1,130 @@
+use hyprsnip_config::{ClipboardSelection, DaemonConfig};
+use hyprsnip_utils::{trim_text, CompiledTrim, Rule, TrimOptions};
+use std::error::Error;
+use std::io::Read as _;
+use std::thread;
+use std::time::Duration;
+use wl_clipboard_rs::copy::{
+    ClipboardType as CopyClipboardType, MimeType as CopyMimeType, Options, Seat as CopySeat, Source,
+};
+use wl_clipboard_rs::paste::{get_contents, ClipboardType, Error as PasteError, MimeType, Seat};
+
+/// Watches the Wayland selection and rewrites it through `trim_text` whenever it changes.
+///
+/// Runs until interrupted. To avoid re-trimming its own output, the daemon remembers the exact
+/// bytes of the last value it wrote and skips anything that matches it. Prompt patterns and
+/// rules are compiled once up front rather than on every clipboard change.
+pub fn run(
+    trim_options: &TrimOptions,
+    rules: &[Rule],
+    daemon: &DaemonConfig,
+    dry_run: bool,
+) -> Result<(), Box<dyn Error>> {
+    let clipboard_type = to_clipboard_type(daemon.clipboard);
+    let compiled = CompiledTrim::new(trim_options.clone(), rules);
+
+    let mut last_seen: Option<String> = None;
+    let mut last_written: Option<String> = None;
+
+    loop {
+        match read_clipboard(clipboard_type) {
+            Ok(Some(current)) => {
+                if last_seen.as_deref() != Some(current.as_str()) {
+                    last_seen = Some(current.clone());
+
+                    thread::sleep(Duration::from_millis(daemon.grace_delay_ms));
+
+                    // Only act if the selection is still the value we saw; otherwise it's
+                    // still settling and we'll catch the final value on a later poll.
+                    match read_clipboard(clipboard_type) {
+                        Ok(Some(settled)) if settled == current => {
+                            if let Err(err) = handle_change(
+                                &settled,
+                                &compiled,
+                                clipboard_type,
+                                dry_run,
+                                &mut last_written,
+                            ) {
+                                eprintln!("hyprsnip: failed to process clipboard change: {err}");
+                            }
+                        }
+                        Ok(_) => {}
+                        Err(err) => eprintln!("hyprsnip: failed to read clipboard: {err}"),
+                    }
+                }
+            }
+            Ok(None) => {}
+            Err(err) => eprintln!("hyprsnip: failed to read clipboard: {err}"),
+        }
+
+        thread::sleep(Duration::from_millis(daemon.poll_interval_ms));
+    }
+}
+
+fn handle_change(
+    current: &str,
+    compiled: &CompiledTrim,
+    clipboard_type: ClipboardType,
+    dry_run: bool,
+    last_written: &mut Option<String>,
+) -> Result<(), Box<dyn Error>> {
+    // The selection we just saw is the thing we wrote last time it changed; it's an echo of
+    // our own write, not a fresh copy from the user.
+    if last_written.as_deref() == Some(current) {
+        return Ok(());
+    }
+
+    let res = trim_text(current, compiled);
+    if !res.changed {
+        return Ok(());
+    }
+
+    if dry_run {
+        println!("would write: {}", res.trimmed);
+        return Ok(());
+    }
+
+    write_clipboard(&res.trimmed, clipboard_type)?;
+    *last_written = Some(res.trimmed);
+    Ok(())
+}
+
+fn read_clipboard(clipboard_type: ClipboardType) -> Result<Option<String>, Box<dyn Error>> {
+    match get_contents(clipboard_type, Seat::Unspecified, MimeType::Text) {
+        Ok((mut pipe, _mime_type)) => {
+            let mut contents = String::new();
+            pipe.read_to_string(&mut contents)?;
+            Ok(Some(contents))
+        }
+        // No text content to trim (e.g. the selection holds an image, a file list, or the
+        // primary selection isn't backed by this compositor) -- treat it the same as an empty
+        // clipboard rather than aborting the daemon.
+        Err(PasteError::NoSeats)
+        | Err(PasteError::ClipboardEmpty)
+        | Err(PasteError::NoMimeType)
+        | Err(PasteError::PrimarySelectionUnsupported) => Ok(None),
+        Err(err) => Err(Box::new(err)),
+    }
+}
+
+fn write_clipboard(text: &str, clipboard_type: ClipboardType) -> Result<(), Box<dyn Error>> {
+    let mut opts = Options::new();
+    opts.clipboard(to_copy_clipboard_type(clipboard_type));
+    opts.seat(CopySeat::Unspecified);
+    opts.copy(Source::Bytes(text.as_bytes().into()), CopyMimeType::Text)?;
+    Ok(())
+}
+
+fn to_clipboard_type(selection: ClipboardSelection) -> ClipboardType {
+    match selection {
+        ClipboardSelection::Regular => ClipboardType::Regular,
+        ClipboardSelection::Primary => ClipboardType::Primary,
+    }
+}
+
+fn to_copy_clipboard_type(clipboard_type: ClipboardType) -> CopyClipboardType {
+    match clipboard_type {
+        ClipboardType::Regular => CopyClipboardType::Regular,
+        ClipboardType::Primary => CopyClipboardType::Primary,
+    }
+}
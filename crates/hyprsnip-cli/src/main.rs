@@ -1,15 +1,18 @@
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
 use hyprsnip_config::Config;
 use hyprsnip_utils::{Aggressiveness, TrimOptions};
 use std::io::Read;
 use std::path::PathBuf;
 use std::process::ExitCode;
 
+mod daemon;
+
 #[derive(Debug, Parser)]
 #[command(name = "hyprsnip")]
 #[command(about = "Wayland clipboard command trimmer", long_about = None)]
 struct Cli {
-    #[arg(long)]
+    #[arg(long, value_hint = clap::ValueHint::FilePath)]
     config: Option<PathBuf>,
 
     #[command(subcommand)]
@@ -27,7 +30,7 @@ enum Command {
         cmd: ConfigCmd,
     },
 
-    /// Clipboard daemon (stub)
+    /// Watch the clipboard and trim pasted commands in place
     Daemon(DaemonArgs),
 
     /// systemd user service helpers (stub)
@@ -35,6 +38,12 @@ enum Command {
         #[command(subcommand)]
         cmd: ServiceCmd,
     },
+
+    /// Generate a shell completion script
+    Completions {
+        #[arg(value_enum)]
+        shell: Shell,
+    },
 }
 
 #[derive(Debug, Parser)]
@@ -57,6 +66,19 @@ struct TrimArgs {
     /// Safety valve (applies here too)
     #[arg(long)]
     max_auto_lines: Option<usize>,
+
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+
+    /// Exit with a non-zero status if the input was skipped rather than trimmed
+    #[arg(long)]
+    fail_on_skip: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
 }
 
 #[derive(Debug, Clone, Copy, clap::ValueEnum)]
@@ -124,9 +146,21 @@ fn run() -> Result<(), Box<dyn std::error::Error>> {
             let mut input = String::new();
             std::io::stdin().read_to_string(&mut input)?;
 
+            let format = args.format;
+            let fail_on_skip = args.fail_on_skip;
             let options = effective_trim_options(config.trim, args);
-            let res = hyprsnip_utils::trim_text(&input, &options);
-            print!("{}", res.trimmed);
+            let compiled = hyprsnip_utils::CompiledTrim::new(options, &config.rules);
+            let res = hyprsnip_utils::trim_text(&input, &compiled);
+
+            match format {
+                OutputFormat::Text => print!("{}", res.trimmed),
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&res)?),
+            }
+
+            if fail_on_skip && res.skipped {
+                return Err("input was skipped".into());
+            }
+
             Ok(())
         }
         Command::Config { cmd } => match cmd {
@@ -144,12 +178,7 @@ fn run() -> Result<(), Box<dyn std::error::Error>> {
             }
         },
         Command::Daemon(args) => {
-            if args.dry_run {
-                println!("daemon not implemented (dry-run)");
-            } else {
-                println!("daemon not implemented");
-            }
-            Ok(())
+            daemon::run(&config.trim, &config.rules, &config.daemon, args.dry_run)
         }
         Command::Service { cmd } => {
             match cmd {
@@ -167,6 +196,12 @@ fn run() -> Result<(), Box<dyn std::error::Error>> {
             }
             Ok(())
         }
+        Command::Completions { shell } => {
+            let mut cmd = Cli::command();
+            let name = cmd.get_name().to_string();
+            clap_complete::generate(shell, &mut cmd, name, &mut std::io::stdout());
+            Ok(())
+        }
     }
 }
 
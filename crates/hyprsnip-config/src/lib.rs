@@ -1,5 +1,5 @@
 use directories::BaseDirs;
-use hyprsnip_utils::TrimOptions;
+use hyprsnip_utils::{Rule, TrimOptions};
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use thiserror::Error;
@@ -40,6 +40,7 @@ impl Default for DaemonConfig {
 pub struct Config {
     pub trim: TrimOptions,
     pub daemon: DaemonConfig,
+    pub rules: Vec<Rule>,
 }
 
 impl Default for Config {
@@ -47,6 +48,7 @@ impl Default for Config {
         Self {
             trim: TrimOptions::default(),
             daemon: DaemonConfig::default(),
+            rules: Vec::new(),
         }
     }
 }
@@ -1,3 +1,4 @@
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -21,6 +22,10 @@ pub struct TrimOptions {
     pub keep_blank_lines: bool,
     pub remove_box_drawing: bool,
     pub max_auto_lines: usize,
+
+    /// Regexes matched against the start of a line's trimmed content; the first one that
+    /// matches has everything through the match stripped, leaving the command body behind.
+    pub prompt_patterns: Vec<String>,
 }
 
 impl Default for TrimOptions {
@@ -30,10 +35,112 @@ impl Default for TrimOptions {
             keep_blank_lines: false,
             remove_box_drawing: true,
             max_auto_lines: 10,
+            prompt_patterns: default_prompt_patterns(),
         }
     }
 }
 
+fn default_prompt_patterns() -> Vec<String> {
+    vec![
+        r"\$ ".to_string(),
+        r"% ".to_string(),
+        r"> ".to_string(),
+        r"# ".to_string(),
+    ]
+}
+
+/// Where a [`Rule`] is applied: against each line independently, or against the whole
+/// (already line-joined) document in one pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleScope {
+    Line,
+    Document,
+}
+
+impl Default for RuleScope {
+    fn default() -> Self {
+        Self::Line
+    }
+}
+
+/// A user-defined find-and-replace transform, applied during `trim_text` alongside the
+/// built-in heuristics. `pattern` is a regex; `replacement` may reference captures with the
+/// usual `$1` / `${name}` syntax.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rule {
+    pub name: String,
+    pub pattern: String,
+    pub replacement: String,
+    #[serde(default)]
+    pub scope: RuleScope,
+    #[serde(default = "default_rule_enabled")]
+    pub enabled: bool,
+}
+
+fn default_rule_enabled() -> bool {
+    true
+}
+
+struct CompiledRule {
+    name: String,
+    regex: Regex,
+    replacement: String,
+    scope: RuleScope,
+}
+
+fn compile_rules(rules: &[Rule]) -> Vec<CompiledRule> {
+    rules
+        .iter()
+        .filter(|rule| rule.enabled)
+        .filter_map(|rule| {
+            Some(CompiledRule {
+                name: rule.name.clone(),
+                regex: Regex::new(&rule.pattern).ok()?,
+                replacement: rule.replacement.clone(),
+                scope: rule.scope,
+            })
+        })
+        .collect()
+}
+
+fn apply_line_rules(lines: &mut [String], rules: &[CompiledRule], fired: &mut Vec<String>) {
+    for line in lines.iter_mut() {
+        for rule in rules.iter().filter(|rule| rule.scope == RuleScope::Line) {
+            let replaced = rule.regex.replace_all(line, rule.replacement.as_str());
+            if replaced != line.as_str() {
+                *line = replaced.into_owned();
+                if !fired.contains(&rule.name) {
+                    fired.push(rule.name.clone());
+                }
+            }
+        }
+    }
+}
+
+fn apply_document_rules(
+    lines: Vec<String>,
+    rules: &[CompiledRule],
+    fired: &mut Vec<String>,
+) -> Vec<String> {
+    let mut text = lines.join("\n");
+
+    for rule in rules
+        .iter()
+        .filter(|rule| rule.scope == RuleScope::Document)
+    {
+        let replaced = rule.regex.replace_all(&text, rule.replacement.as_str());
+        if replaced != text {
+            text = replaced.into_owned();
+            if !fired.contains(&rule.name) {
+                fired.push(rule.name.clone());
+            }
+        }
+    }
+
+    text.split('\n').map(|line| line.to_string()).collect()
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(default)]
 pub struct TrimResult {
@@ -56,7 +163,32 @@ impl Default for TrimResult {
     }
 }
 
-pub fn trim_text(input: &str, options: &TrimOptions) -> TrimResult {
+/// A [`TrimOptions`] and its [`Rule`]s, compiled ahead of time.
+///
+/// Building one of these compiles every prompt pattern and rule regex exactly once; callers
+/// that invoke `trim_text` repeatedly with the same options and rules (e.g. the daemon, once
+/// per clipboard change) should build a single `CompiledTrim` up front and reuse it, rather
+/// than recompiling the same regexes on every call.
+pub struct CompiledTrim {
+    options: TrimOptions,
+    prompt_patterns: Vec<Regex>,
+    rules: Vec<CompiledRule>,
+}
+
+impl CompiledTrim {
+    pub fn new(options: TrimOptions, rules: &[Rule]) -> Self {
+        let prompt_patterns = compile_prompt_patterns(&options.prompt_patterns);
+        let rules = compile_rules(rules);
+        Self {
+            options,
+            prompt_patterns,
+            rules,
+        }
+    }
+}
+
+pub fn trim_text(input: &str, compiled: &CompiledTrim) -> TrimResult {
+    let options = &compiled.options;
     let original = input.to_string();
     let line_count = input.lines().count();
 
@@ -81,7 +213,15 @@ pub fn trim_text(input: &str, options: &TrimOptions) -> TrimResult {
         }
     }
 
-    strip_prompt_prefix_in_place(&mut lines, options.aggressiveness);
+    let mut fired_rules: Vec<String> = Vec::new();
+    apply_line_rules(&mut lines, &compiled.rules, &mut fired_rules);
+    let mut lines = apply_document_rules(lines, &compiled.rules, &mut fired_rules);
+
+    strip_prompt_prefix_in_place(
+        &mut lines,
+        options.aggressiveness,
+        &compiled.prompt_patterns,
+    );
 
     let trimmed = if options.keep_blank_lines {
         flatten_preserving_blank_lines(&lines)
@@ -96,7 +236,11 @@ pub fn trim_text(input: &str, options: &TrimOptions) -> TrimResult {
         trimmed,
         changed,
         skipped: false,
-        reason: None,
+        reason: if changed && !fired_rules.is_empty() {
+            Some(format!("rules applied: {}", fired_rules.join(", ")))
+        } else {
+            None
+        },
     }
 }
 
@@ -123,7 +267,15 @@ fn strip_box_drawing(line: &str) -> String {
     s
 }
 
-fn strip_prompt_prefix_in_place(lines: &mut [String], aggressiveness: Aggressiveness) {
+fn strip_prompt_prefix_in_place(
+    lines: &mut [String],
+    aggressiveness: Aggressiveness,
+    patterns: &[Regex],
+) {
+    if patterns.is_empty() {
+        return;
+    }
+
     let Some((idx, line)) = lines
         .iter_mut()
         .enumerate()
@@ -133,17 +285,11 @@ fn strip_prompt_prefix_in_place(lines: &mut [String], aggressiveness: Aggressive
     };
 
     let trimmed = line.trim_start();
-    let Some((prefix, rest)) = trimmed
-        .strip_prefix("$ ")
-        .map(|rest| ("$ ", rest))
-        .or_else(|| trimmed.strip_prefix("% ").map(|rest| ("% ", rest)))
-        .or_else(|| trimmed.strip_prefix("> ").map(|rest| ("> ", rest)))
-        .or_else(|| trimmed.strip_prefix("# ").map(|rest| ("# ", rest)))
-    else {
+    let Some((matched, rest)) = find_prompt_match(trimmed, patterns) else {
         return;
     };
 
-    if prefix == "# " && looks_like_markdown_heading(trimmed) {
+    if matched == "# " && looks_like_markdown_heading(trimmed) {
         return;
     }
 
@@ -160,12 +306,7 @@ fn strip_prompt_prefix_in_place(lines: &mut [String], aggressiveness: Aggressive
     // subsequent lines for multi-line copies.
     for later in lines.iter_mut().skip(idx + 1) {
         let later_trimmed = later.trim_start();
-        let Some(rest) = later_trimmed
-            .strip_prefix("$ ")
-            .or_else(|| later_trimmed.strip_prefix("% "))
-            .or_else(|| later_trimmed.strip_prefix("> "))
-            .or_else(|| later_trimmed.strip_prefix("# "))
-        else {
+        let Some((_, rest)) = find_prompt_match(later_trimmed, patterns) else {
             continue;
         };
 
@@ -175,6 +316,25 @@ fn strip_prompt_prefix_in_place(lines: &mut [String], aggressiveness: Aggressive
     }
 }
 
+/// Compiles each user pattern anchored at the start of the (already-trimmed) line. Patterns
+/// that fail to compile are skipped rather than rejected wholesale, since one bad regex in a
+/// user's config shouldn't take down prompt stripping entirely.
+fn compile_prompt_patterns(prompt_patterns: &[String]) -> Vec<Regex> {
+    prompt_patterns
+        .iter()
+        .filter_map(|pattern| Regex::new(&format!("^(?:{pattern})")).ok())
+        .collect()
+}
+
+/// Returns the matched text and the remainder of `trimmed` after it, using the first pattern
+/// (in config order) that matches at the start of the line.
+fn find_prompt_match<'a>(trimmed: &'a str, patterns: &[Regex]) -> Option<(&'a str, &'a str)> {
+    patterns.iter().find_map(|re| {
+        let m = re.find(trimmed)?;
+        Some((m.as_str(), &trimmed[m.end()..]))
+    })
+}
+
 fn looks_like_markdown_heading(line: &str) -> bool {
     let Some(rest) = line.strip_prefix("# ") else {
         return false;
@@ -290,11 +450,15 @@ fn flatten_group(group: &[&str]) -> String {
 mod tests {
     use super::*;
 
+    fn compiled(options: TrimOptions, rules: &[Rule]) -> CompiledTrim {
+        CompiledTrim::new(options, rules)
+    }
+
     #[test]
     fn flattens_backslash_continuations() {
         let input = "kubectl get pods \\\n  -n kube-system \\\n  | jq '.items[].metadata.name'\n";
 
-        let res = trim_text(input, &TrimOptions::default());
+        let res = trim_text(input, &compiled(TrimOptions::default(), &[]));
         assert_eq!(
             res.trimmed,
             "kubectl get pods -n kube-system | jq '.items[].metadata.name'"
@@ -305,21 +469,77 @@ mod tests {
     #[test]
     fn strips_box_drawing_gutters() {
         let input = "  ┃  hello\n┃  world  \n";
-        let res = trim_text(input, &TrimOptions::default());
+        let res = trim_text(input, &compiled(TrimOptions::default(), &[]));
         assert_eq!(res.trimmed, "hello world");
     }
 
     #[test]
     fn keeps_markdown_heading() {
         let input = "# Release Notes\n";
-        let res = trim_text(input, &TrimOptions::default());
+        let res = trim_text(input, &compiled(TrimOptions::default(), &[]));
         assert_eq!(res.trimmed, "# Release Notes");
     }
 
     #[test]
     fn strips_shell_prompt_on_commands() {
         let input = "$ brew install foo\n";
-        let res = trim_text(input, &TrimOptions::default());
+        let res = trim_text(input, &compiled(TrimOptions::default(), &[]));
         assert_eq!(res.trimmed, "brew install foo");
     }
+
+    #[test]
+    fn strips_custom_prompt_pattern() {
+        let mut options = TrimOptions::default();
+        options.prompt_patterns = vec![r"[\w.-]+@[\w.-]+:[^$]*\$ ".to_string()];
+
+        let input = "user@host:~/dir$ cargo build --release\n";
+        let res = trim_text(input, &compiled(options, &[]));
+        assert_eq!(res.trimmed, "cargo build --release");
+    }
+
+    #[test]
+    fn applies_line_scoped_rule_and_records_reason() {
+        let rule = Rule {
+            name: "strip_venv".to_string(),
+            pattern: r"^\(venv\) ".to_string(),
+            replacement: String::new(),
+            scope: RuleScope::Line,
+            enabled: true,
+        };
+
+        let input = "(venv) pip install requests\n";
+        let res = trim_text(input, &compiled(TrimOptions::default(), &[rule]));
+        assert_eq!(res.trimmed, "pip install requests");
+        assert_eq!(res.reason.as_deref(), Some("rules applied: strip_venv"));
+    }
+
+    #[test]
+    fn applies_document_scoped_rule() {
+        let rule = Rule {
+            name: "drop_timestamps".to_string(),
+            pattern: r"(?m)^\d{4}-\d{2}-\d{2}T[\d:.]+Z ".to_string(),
+            replacement: String::new(),
+            scope: RuleScope::Document,
+            enabled: true,
+        };
+
+        let input = "2024-01-01T00:00:00Z some log line\n";
+        let res = trim_text(input, &compiled(TrimOptions::default(), &[rule]));
+        assert_eq!(res.trimmed, "some log line");
+    }
+
+    #[test]
+    fn disabled_rule_does_not_fire() {
+        let rule = Rule {
+            name: "noop".to_string(),
+            pattern: r"^sudo -E ".to_string(),
+            replacement: String::new(),
+            scope: RuleScope::Line,
+            enabled: false,
+        };
+
+        let input = "sudo -E make install\n";
+        let res = trim_text(input, &compiled(TrimOptions::default(), &[rule]));
+        assert_eq!(res.trimmed, "sudo -E make install");
+    }
 }